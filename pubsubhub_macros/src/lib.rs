@@ -20,7 +20,30 @@ use quote::quote;
 /// After macro expansion, the `PubSubHub` struct now has methods for `subscribe_A`, `subscribe_B`,
 /// `publish_A`, etc. There are struct fields for `__subscriptions_A`, etc, which track the
 /// subscribers to various events.
-/// 
+///
+/// It also gets a few convenience methods per event: `subscribe_A_once` (subscribes a handler
+/// that is automatically removed right after its first `receive`), `clear_A` (drops every `A`
+/// subscriber), and `subscription_count_A` (the number of subscribers currently registered for
+/// `A`). A single `clear_all` method empties every generated subscription list at once.
+///
+/// A `publish_A_from(&self, p: &A, ctx: &PublishContext)` is generated alongside `publish_A`; it
+/// calls each subscriber's `receive_with` instead of `receive`, forwarding a `PublishContext` that
+/// can carry the sender's identity and arbitrary metadata. Subscribers that don't override
+/// `receive_with` still get their `receive` called via its default implementation.
+///
+/// A synchronous hub also gets `publish_A_checked(&self, p: &A) -> Vec<SubscriberError>`, which
+/// wraps each subscriber's `receive` in `catch_unwind` so a panicking subscriber doesn't stop
+/// delivery to the rest; it returns a `SubscriberError` per subscriber that panicked instead of
+/// propagating. `publish_A` itself still propagates panics, as it always has.
+///
+/// # Async mode
+/// Prefixing the argument list with `async`, e.g. `#[publishes(async, A, B)]`, switches the
+/// generated code over to [`AsyncSubscriber`](../pubsubhub/trait.AsyncSubscriber.html): subscribers
+/// are stored as `Box<dyn AsyncSubscriber<A> + Send>`, and `publish_A` becomes an `async fn` that
+/// awaits each subscriber's `receive` in turn. A `publish_A_concurrent` async fn is also generated,
+/// which drives every subscriber's `receive` future concurrently via `FuturesUnordered` instead of
+/// one at a time.
+///
 /// # Notes
 /// The struct tagged with `publishes` will have additional fields and methods added to it.
 /// Existing code will be preserved, so you can add additional fields to your PubSub hub, although
@@ -28,12 +51,13 @@ use quote::quote;
 #[proc_macro_attribute]
 pub fn publishes(args: TokenStream, item: TokenStream) -> TokenStream {
     let item_struct: ItemStruct = parse_macro_input!(item as ItemStruct);
+    let (is_async, args) = pubsubhub_macros::strip_async_marker(args);
     let publishables = parse_macro_input!(args as AttributeArgs);
     let struct_ident = item_struct.ident.clone();
 
     let names_map = pubsubhub_macros::build_names_map(publishables);
 
-    let all_together = pubsubhub_macros::construct_new_struct(&names_map, struct_ident, item_struct);
+    let all_together = pubsubhub_macros::construct_new_struct(&names_map, struct_ident, item_struct, is_async);
 
     all_together
 }
@@ -64,6 +88,29 @@ mod pubsubhub_macros {
     use quote::{quote, format_ident};
     use syn::{NestedMeta, Ident, Meta, parse::Parser, ItemStruct};
 
+    /// Looks for a leading bare `async` token in the `publishes(...)` argument list and strips it
+    /// off, since `async` is a keyword and can't be parsed as a `syn::Path` alongside the event
+    /// idents. Returns whether the marker was present and the remaining tokens to parse as
+    /// `AttributeArgs`.
+    pub(super) fn strip_async_marker(args: TokenStream) -> (bool, TokenStream) {
+        let mut tokens = proc_macro2::TokenStream::from(args).into_iter().peekable();
+
+        let is_async = match tokens.peek() {
+            Some(proc_macro2::TokenTree::Ident(ident)) if ident == "async" => {
+                tokens.next();
+                if let Some(proc_macro2::TokenTree::Punct(p)) = tokens.peek() {
+                    if p.as_char() == ',' {
+                        tokens.next();
+                    }
+                }
+                true
+            }
+            _ => false,
+        };
+
+        (is_async, tokens.collect::<proc_macro2::TokenStream>().into())
+    }
+
     pub(super) fn build_names_map(names: Vec<NestedMeta>) -> HashMap<Ident, usize> {
         let mut names_map: HashMap<Ident, usize> = HashMap::new();
         let mut count = 0;
@@ -88,55 +135,208 @@ mod pubsubhub_macros {
         return names_map;
     }
 
-    pub(super) fn construct_new_struct(names_map: &HashMap<Ident, usize>, struct_ident: Ident, mut item_struct: ItemStruct) -> TokenStream {
+    pub(super) fn construct_new_struct(names_map: &HashMap<Ident, usize>, struct_ident: Ident, mut item_struct: ItemStruct, is_async: bool) -> TokenStream {
         let mut all_impls = quote! {};
         let mut constructor_impl_body = quote! {};
+        let mut clear_all_body = quote! {};
 
         for pair in names_map {
             let ident = pair.0;
             let subscriptions_ident = format_ident!("__subscriptions_{}", ident);
             let subscribe_fn_ident = format_ident!("subscribe_{}", ident);
+            let subscribe_once_fn_ident = format_ident!("subscribe_{}_once", ident);
             let publish_fn_ident = format_ident!("publish_{}", ident);
-            let unsubscribe_fn_ident = format_ident!("unsubscribe_{}", ident);
-    
-            let struct_field_code = quote! {
-                #subscriptions_ident: Vec<std::sync::Arc<std::sync::Mutex<Box<dyn Subscriber<#ident>>>>>
-            }.into();
-    
-            let pubsub_functions_code = quote! {
-                impl #struct_ident {
-                    #[allow(non_snake_case)]
-                    pub fn #subscribe_fn_ident(&mut self, s: Box<dyn Subscriber<#ident>>) -> std::sync::Arc<std::sync::Mutex<Box<dyn Subscriber<#ident>>>> {
-                        let arced = std::sync::Arc::new(std::sync::Mutex::new(s));
-                        self.#subscriptions_ident.push(arced.clone());
-                        return arced;
-                    }
-    
-                    #[allow(non_snake_case)]
-                    pub fn #publish_fn_ident(&self, p: &#ident) {
-                        for sub_container in self.#subscriptions_ident.iter() {
-                            (*sub_container.lock().unwrap().as_mut()).receive(p);
+            let publish_from_fn_ident = format_ident!("publish_{}_from", ident);
+            let publish_checked_fn_ident = format_ident!("publish_{}_checked", ident);
+            let clear_fn_ident = format_ident!("clear_{}", ident);
+            let subscription_count_fn_ident = format_ident!("subscription_count_{}", ident);
+
+            let (struct_field_code, pubsub_functions_code) = if is_async {
+                let publish_fn_concurrent_ident = format_ident!("publish_{}_concurrent", ident);
+
+                let struct_field_code = quote! {
+                    #subscriptions_ident: std::sync::Arc<std::sync::Mutex<Vec<std::sync::Arc<tokio::sync::Mutex<Box<dyn AsyncSubscriber<#ident> + Send>>>>>>
+                }.into();
+
+                let pubsub_functions_code = quote! {
+                    impl #struct_ident {
+                        #[allow(non_snake_case)]
+                        pub fn #subscribe_fn_ident(&mut self, s: Box<dyn AsyncSubscriber<#ident> + Send>) -> AsyncSubscription<#ident> {
+                            let arced = std::sync::Arc::new(tokio::sync::Mutex::new(s));
+                            self.#subscriptions_ident.lock().unwrap().push(arced.clone());
+                            AsyncSubscription::new(arced, std::sync::Arc::downgrade(&self.#subscriptions_ident))
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub fn #subscribe_once_fn_ident(&mut self, s: Box<dyn AsyncSubscriber<#ident> + Send>) -> AsyncSubscription<#ident> {
+                            self.#subscribe_fn_ident(Box::new(AsyncOnceSubscriber::new(s)))
+                        }
+
+                        // The outer `__subscriptions_A` lock is a plain `std::sync::Mutex` and is only ever
+                        // held long enough to snapshot the current `Arc` handles, never across an `.await` --
+                        // holding it through a subscriber's `receive` would make this future `!Send` and risk
+                        // deadlocking against a subscriber that unsubscribes itself (or another publish) while
+                        // it runs. Each subscriber's own `tokio::sync::Mutex` is held across its `.await`
+                        // instead, which is the async-aware equivalent and safe to do.
+                        #[allow(non_snake_case)]
+                        pub async fn #publish_fn_ident(&self, p: &#ident) {
+                            let subs: Vec<_> = self.#subscriptions_ident.lock().unwrap().clone();
+                            let mut fired = Vec::new();
+                            for sub_container in subs.iter() {
+                                let mut guard = sub_container.lock().await;
+                                guard.as_mut().receive(p).await;
+                                if guard.as_any().downcast_ref::<AsyncOnceSubscriber<#ident>>().map_or(false, |o| o.fired()) {
+                                    fired.push(sub_container.clone());
+                                }
+                            }
+                            sweep_fired_once(&self.#subscriptions_ident, fired);
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub async fn #publish_from_fn_ident(&self, p: &#ident, ctx: &PublishContext<'_>) {
+                            let subs: Vec<_> = self.#subscriptions_ident.lock().unwrap().clone();
+                            let mut fired = Vec::new();
+                            for sub_container in subs.iter() {
+                                let mut guard = sub_container.lock().await;
+                                guard.as_mut().receive_with(p, ctx).await;
+                                if guard.as_any().downcast_ref::<AsyncOnceSubscriber<#ident>>().map_or(false, |o| o.fired()) {
+                                    fired.push(sub_container.clone());
+                                }
+                            }
+                            sweep_fired_once(&self.#subscriptions_ident, fired);
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub async fn #publish_fn_concurrent_ident(&self, p: &#ident) {
+                            use futures::stream::StreamExt;
+
+                            let subs: Vec<_> = self.#subscriptions_ident.lock().unwrap().clone();
+                            let mut in_flight: futures::stream::FuturesUnordered<_> = subs
+                                .iter()
+                                .map(|sub_container| async move {
+                                    let mut guard = sub_container.lock().await;
+                                    guard.as_mut().receive(p).await;
+                                    if guard.as_any().downcast_ref::<AsyncOnceSubscriber<#ident>>().map_or(false, |o| o.fired()) {
+                                        Some(sub_container.clone())
+                                    } else {
+                                        None
+                                    }
+                                })
+                                .collect();
+
+                            let mut fired = Vec::new();
+                            while let Some(result) = in_flight.next().await {
+                                if let Some(sub_container) = result {
+                                    fired.push(sub_container);
+                                }
+                            }
+                            sweep_fired_once(&self.#subscriptions_ident, fired);
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub fn #clear_fn_ident(&self) {
+                            self.#subscriptions_ident.lock().unwrap().clear();
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub fn #subscription_count_fn_ident(&self) -> usize {
+                            self.#subscriptions_ident.lock().unwrap().len()
                         }
                     }
+                };
+
+                (struct_field_code, pubsub_functions_code)
+            } else {
+                let struct_field_code = quote! {
+                    #subscriptions_ident: std::sync::Arc<std::sync::Mutex<Vec<std::sync::Arc<std::sync::Mutex<Box<dyn Subscriber<#ident>>>>>>>
+                }.into();
+
+                let pubsub_functions_code = quote! {
+                    impl #struct_ident {
+                        #[allow(non_snake_case)]
+                        pub fn #subscribe_fn_ident(&mut self, s: Box<dyn Subscriber<#ident>>) -> Subscription<std::sync::Mutex<Box<dyn Subscriber<#ident>>>> {
+                            let arced = std::sync::Arc::new(std::sync::Mutex::new(s));
+                            self.#subscriptions_ident.lock().unwrap().push(arced.clone());
+                            Subscription::new(arced, std::sync::Arc::downgrade(&self.#subscriptions_ident))
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub fn #subscribe_once_fn_ident(&mut self, s: Box<dyn Subscriber<#ident>>) -> Subscription<std::sync::Mutex<Box<dyn Subscriber<#ident>>>> {
+                            self.#subscribe_fn_ident(Box::new(OnceSubscriber::new(s)))
+                        }
 
-                    #[allow(non_snake_case)]
-                    fn #unsubscribe_fn_ident(&mut self, s_arc: &std::sync::Arc<std::sync::Mutex<Box<dyn Subscriber<#ident>>>>) {
-                        let mut idx_to_remove = None;
-                        for (idx, sub_container) in self.#subscriptions_ident.iter().enumerate() {
-                            if std::sync::Arc::ptr_eq(&s_arc, sub_container) {
-                                idx_to_remove = Some(idx);
-                                break;
+                        // Snapshots the subscriber Arcs out of __subscriptions_A and drops that lock before
+                        // calling into any of them: a subscriber's receive may itself drop a Subscription
+                        // (its own, another's, or via clear_A/clear_all) which needs this same lock, so
+                        // holding it across receive would deadlock the publishing thread against itself.
+                        #[allow(non_snake_case)]
+                        pub fn #publish_fn_ident(&self, p: &#ident) {
+                            let subs: Vec<_> = self.#subscriptions_ident.lock().unwrap().clone();
+                            let mut fired = Vec::new();
+                            for sub_container in subs.iter() {
+                                let mut guard = sub_container.lock().unwrap();
+                                (*guard.as_mut()).receive(p);
+                                if guard.as_any().downcast_ref::<OnceSubscriber<#ident>>().map_or(false, |o| o.fired()) {
+                                    fired.push(sub_container.clone());
+                                }
                             }
+                            sweep_fired_once(&self.#subscriptions_ident, fired);
                         }
-                        if let Some(idx) = idx_to_remove {
-                            self.#subscriptions_ident.swap_remove(idx);
+
+                        #[allow(non_snake_case)]
+                        pub fn #publish_from_fn_ident(&self, p: &#ident, ctx: &PublishContext) {
+                            let subs: Vec<_> = self.#subscriptions_ident.lock().unwrap().clone();
+                            let mut fired = Vec::new();
+                            for sub_container in subs.iter() {
+                                let mut guard = sub_container.lock().unwrap();
+                                (*guard.as_mut()).receive_with(p, ctx);
+                                if guard.as_any().downcast_ref::<OnceSubscriber<#ident>>().map_or(false, |o| o.fired()) {
+                                    fired.push(sub_container.clone());
+                                }
+                            }
+                            sweep_fired_once(&self.#subscriptions_ident, fired);
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub fn #publish_checked_fn_ident(&self, p: &#ident) -> Vec<SubscriberError> {
+                            let subs: Vec<_> = self.#subscriptions_ident.lock().unwrap().clone();
+                            let mut errors = Vec::new();
+                            let mut fired = Vec::new();
+                            for (idx, sub_container) in subs.iter().enumerate() {
+                                let mut guard = sub_container.lock().unwrap();
+                                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                                    (*guard.as_mut()).receive(p);
+                                }));
+                                match result {
+                                    Ok(()) => {
+                                        if guard.as_any().downcast_ref::<OnceSubscriber<#ident>>().map_or(false, |o| o.fired()) {
+                                            fired.push(sub_container.clone());
+                                        }
+                                    }
+                                    Err(panic) => errors.push(SubscriberError::new(idx, panic)),
+                                }
+                            }
+                            sweep_fired_once(&self.#subscriptions_ident, fired);
+                            errors
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub fn #clear_fn_ident(&self) {
+                            self.#subscriptions_ident.lock().unwrap().clear();
+                        }
+
+                        #[allow(non_snake_case)]
+                        pub fn #subscription_count_fn_ident(&self) -> usize {
+                            self.#subscriptions_ident.lock().unwrap().len()
                         }
                     }
-                }
+                };
+
+                (struct_field_code, pubsub_functions_code)
             };
-    
+
             let struct_init_code = quote! {
-                #subscriptions_ident: Vec::new(),
+                #subscriptions_ident: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
             };
     
             // Now I need to add the new struct code to the struct.
@@ -158,20 +358,30 @@ mod pubsubhub_macros {
                 #constructor_impl_body
                 #struct_init_code
             };
+
+            clear_all_body = quote!{
+                #clear_all_body
+                self.#subscriptions_ident.lock().unwrap().clear();
+            };
         }
-    
+
         let all_together = quote!{
             #[allow(non_snake_case)]
             #item_struct
-            
+
             impl #struct_ident {
                 pub fn new() -> Self {
                     Self {
                         #constructor_impl_body
                     }
                 }
+
+                /// Empties every generated subscription list on this hub in one call.
+                pub fn clear_all(&self) {
+                    #clear_all_body
+                }
             }
-    
+
             #all_impls
         }.into();
         return all_together;
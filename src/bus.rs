@@ -0,0 +1,99 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::{Subscriber, Subscription};
+
+/// A runtime, type-erased alternative to the `#[publishes(...)]` macro.
+///
+/// `#[publishes(A, B)]` bakes a fixed set of event types into a struct at compile time: adding an
+/// event means editing the attribute's argument list and recompiling the hub. `Bus` instead keys
+/// its subscriber lists by `TypeId`, so independent modules can register event types the hub
+/// didn't know about when it was created, and compose onto a single shared `Bus` at runtime. The
+/// macro path remains the better choice when the set of events is known up front, since it avoids
+/// the downcast on every `publish`.
+///
+/// # Examples
+/// ```
+/// # use pubsubhub::{Bus, Subscriber};
+/// # use pubsubhub_macros::as_any;
+/// struct Food { amount: i32 }
+///
+/// struct Dog { total_eaten: i32 }
+/// impl Subscriber<Food> for Dog {
+///     fn receive(&mut self, event: &Food) {
+///         self.total_eaten += event.amount;
+///     }
+///     as_any!();
+/// }
+///
+/// let bus = Bus::new();
+/// let sub = bus.subscribe(Box::new(Dog { total_eaten: 0 }));
+/// bus.publish(&Food { amount: 3 });
+///
+/// let boxed = sub.lock().unwrap();
+/// assert_eq!(boxed.as_any().downcast_ref::<Dog>().unwrap().total_eaten, 3);
+/// ```
+///
+/// `Bus` is itself `!Send`/`!Sync`: [`Subscriber`] carries no `Send` bound (a subscriber may hold
+/// non-`Send` state, same as a macro-generated hub), so the type-erased channel storage can't
+/// require `Send` either.
+pub struct Bus {
+    channels: Mutex<HashMap<TypeId, Box<dyn Any>>>,
+}
+
+type Channel<E> = Arc<Mutex<Vec<Arc<Mutex<Box<dyn Subscriber<E>>>>>>>;
+
+impl Bus {
+    /// Creates an empty `Bus` with no registered channels.
+    pub fn new() -> Self {
+        Self { channels: Mutex::new(HashMap::new()) }
+    }
+
+    fn channel<E: 'static>(&self) -> Channel<E> {
+        let mut channels = self.channels.lock().unwrap();
+        let erased = channels
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(Channel::<E>::new(Mutex::new(Vec::new()))));
+        erased
+            .downcast_ref::<Channel<E>>()
+            .expect("Bus: TypeId collision with mismatched Subscriber<E> storage")
+            .clone()
+    }
+
+    /// Subscribes `s` to events of type `E`, registering the `TypeId` channel for `E` the first
+    /// time it's used. Returns the same [`Subscription`] guard the `publishes` macro returns, so
+    /// the subscriber is unsubscribed when the guard is dropped (or kept forever via
+    /// [`Subscription::forget`]).
+    ///
+    /// Takes `&self`, not `&mut self`: the internal `Mutex` already makes concurrent access safe,
+    /// and requiring exclusive access here would force external synchronization between
+    /// independent modules subscribing onto the same shared `Bus`, defeating the point of it.
+    pub fn subscribe<E: 'static>(&self, s: Box<dyn Subscriber<E>>) -> Subscription<Mutex<Box<dyn Subscriber<E>>>> {
+        let channel = self.channel::<E>();
+        let arced = Arc::new(Mutex::new(s));
+        channel.lock().unwrap().push(arced.clone());
+        Subscription::new(arced, Arc::downgrade(&channel))
+    }
+
+    /// Publishes `e` to every subscriber registered for `E`. A no-op if nothing has subscribed to
+    /// `E` yet.
+    ///
+    /// Snapshots the channel's subscriber `Arc`s before iterating, rather than holding the
+    /// channel's lock across `receive`: a subscriber's `receive` may itself drop a `Subscription`
+    /// for the same channel, which needs that same lock to unsubscribe, and holding it here would
+    /// deadlock the publishing thread against itself.
+    pub fn publish<E: 'static>(&self, e: &E) {
+        let channel = self.channel::<E>();
+        let subs: Vec<_> = channel.lock().unwrap().clone();
+        for sub_container in subs.iter() {
+            (*sub_container.lock().unwrap().as_mut()).receive(e);
+        }
+    }
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
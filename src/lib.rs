@@ -1,5 +1,58 @@
+mod bus;
+
+pub use bus::Bus;
+
+/// Removes `fired` from `container` by identity, locking it only if there's actually something
+/// to remove. Shared by every generated `publish_A`/`publish_A_from`/`publish_A_checked` variant
+/// so the mark-and-sweep removal of fired `subscribe_A_once` subscribers is written once instead
+/// of once per generated function.
+///
+/// Takes the fired subscribers themselves rather than their positions in `container` at mark
+/// time: the mark pass and this sweep necessarily take the container's lock separately (the mark
+/// pass is done with it by the time a subscriber reports itself fired), so another thread can
+/// subscribe, unsubscribe, or publish in between. Removing by `Arc::ptr_eq` means that race can
+/// only make the sweep a no-op for a subscriber that's already gone; stale positions could instead
+/// remove the wrong (still-live) subscriber or panic on an out-of-range index.
+#[doc(hidden)]
+pub fn sweep_fired_once<T>(container: &std::sync::Mutex<Vec<std::sync::Arc<T>>>, fired: Vec<std::sync::Arc<T>>) {
+    if fired.is_empty() {
+        return;
+    }
+    let mut subs = container.lock().unwrap();
+    subs.retain(|s| !fired.iter().any(|f| std::sync::Arc::ptr_eq(s, f)));
+}
+
+/// Describes one subscriber's failure to handle an event during a `publish_A_checked` call.
+///
+/// `publish_A_checked` keeps delivering to the remaining subscribers even after one panics, so a
+/// single misbehaving handler can't block everyone after it in the subscription list. Each panic
+/// is captured as a `SubscriberError` instead of propagating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriberError {
+    /// The index of the failing subscriber within the hub's subscription list as it stood when the
+    /// `publish_A_checked` call began.
+    pub index: usize,
+    /// The panic payload, downcast to a `String` where possible (`&str` and `String` payloads, as
+    /// produced by `panic!` and `assert!`), or a placeholder message otherwise.
+    pub message: String,
+}
+
+impl SubscriberError {
+    /// Builds a `SubscriberError` from a `std::panic::catch_unwind` payload.
+    pub fn new(index: usize, panic: Box<dyn std::any::Any + Send>) -> Self {
+        let message = if let Some(s) = panic.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = panic.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "subscriber panicked with a non-string payload".to_string()
+        };
+        Self { index, message }
+    }
+}
+
 /// A `Subscriber` can subscribe to events of type `E` that are published.
-/// 
+///
 /// Subscribers must implement the `receive()` method, which is called with a borrowed reference to
 /// the `E` object that was published. They must also implement `as_any()`, but this crate provides
 /// the `as_any!()` macro to cut down boilerplate. 
@@ -54,6 +107,16 @@ pub trait Subscriber<E> {
     /// published.
     fn receive(&mut self, event: &E);
 
+    /// Called instead of [`receive`](Subscriber::receive) when the event is published through a
+    /// generated `publish_A_from` method, which forwards a [`PublishContext`] carrying the
+    /// publisher's identity and any out-of-band metadata alongside the event. The default
+    /// implementation ignores `ctx` and delegates to `receive`, so existing `impl`s keep
+    /// compiling unchanged.
+    fn receive_with(&mut self, event: &E, ctx: &PublishContext) {
+        let _ = ctx;
+        self.receive(event)
+    }
+
     /// `as_any` provides a cast from this type to `std::any::Any`. This isn't directly used by
     /// a PubSub system but may be useful if you want to recover a reference to the object after
     /// subscribing it to a PubSub.
@@ -90,4 +153,307 @@ pub trait Subscriber<E> {
     /// }
     /// ```
     fn as_any(&self) -> &dyn std::any::Any;
+}
+
+/// `AsyncSubscriber` is the asynchronous counterpart to [`Subscriber`].
+///
+/// A hub declared with `#[publishes(async, A, B)]` calls `receive` as an `async fn` instead of a
+/// plain one, so handlers can `.await` I/O (database writes, network calls, etc) instead of being
+/// forced to block the publisher for the whole fan-out. Because `async fn` in traits isn't
+/// object-safe on its own, `AsyncSubscriber` is defined with `#[async_trait]` so it can still be
+/// stored as `Box<dyn AsyncSubscriber<E> + Send>`; implementers need to annotate their `impl`
+/// blocks with `#[async_trait]` as well.
+///
+/// # Examples
+///
+/// ```
+/// # use pubsubhub_macros::as_any;
+/// # use pubsubhub::AsyncSubscriber;
+/// # use async_trait::async_trait;
+///
+/// struct Food { amount: i32 }
+///
+/// struct Dog { total_eaten: i32 }
+///
+/// #[async_trait]
+/// impl AsyncSubscriber<Food> for Dog {
+///     async fn receive(&mut self, event: &Food) {
+///         self.total_eaten += event.amount;
+///     }
+///     as_any!();
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait AsyncSubscriber<E: Send + Sync>: Send {
+    /// This method is called for each subscriber in an async-mode `PubSubHub` when an event of
+    /// type `E` is published. Unlike [`Subscriber::receive`], it may `.await` other work.
+    async fn receive(&mut self, event: &E);
+
+    /// The async counterpart to [`Subscriber::receive_with`]; see its documentation. The default
+    /// implementation ignores `ctx` and delegates to `receive`.
+    async fn receive_with(&mut self, event: &E, ctx: &PublishContext) {
+        let _ = ctx;
+        self.receive(event).await
+    }
+
+    /// `as_any` provides a cast from this type to `std::any::Any`. See [`Subscriber::as_any`] for
+    /// the same downcasting use case.
+    fn as_any(&self) -> &dyn std::any::Any;
+}
+
+/// Out-of-band context forwarded alongside an event when it is published through a generated
+/// `publish_A_from` method, following the common `publish(name, sender, args)` pattern: subscribers
+/// can see *who* published the event and read arbitrary metadata without the event type itself
+/// having to carry routing information.
+///
+/// # Examples
+/// ```
+/// # use pubsubhub::PublishContext;
+/// struct Publisher;
+/// let publisher = Publisher;
+///
+/// let ctx = PublishContext::new()
+///     .with_sender(&publisher)
+///     .with_metadata("reason", "retry");
+///
+/// assert!(ctx.sender().is_some());
+/// assert_eq!(ctx.metadata().get("reason").map(String::as_str), Some("retry"));
+/// ```
+#[derive(Default)]
+pub struct PublishContext<'a> {
+    sender: Option<&'a (dyn std::any::Any + Send + Sync)>,
+    metadata: std::collections::HashMap<String, String>,
+}
+
+impl<'a> PublishContext<'a> {
+    /// Creates an empty context: no sender, no metadata.
+    pub fn new() -> Self {
+        Self { sender: None, metadata: std::collections::HashMap::new() }
+    }
+
+    /// Attaches a reference to whatever published the event. Subscribers can recover the concrete
+    /// type via `sender().and_then(Any::downcast_ref)`.
+    ///
+    /// The sender must be `Send + Sync`: `#[async_trait]` boxes `AsyncSubscriber::receive_with`'s
+    /// future as `Pin<Box<dyn Future<Output = ()> + Send>>`, so a non-`Sync` sender captured by
+    /// reference would make that future itself non-`Send` and fail to compile wherever
+    /// `publish_A_from` is used on an async-mode hub.
+    pub fn with_sender(mut self, sender: &'a (dyn std::any::Any + Send + Sync)) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Adds a metadata key/value pair, for routing or filtering information that doesn't belong
+    /// on the event type itself.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// The sender attached via [`with_sender`](PublishContext::with_sender), if any.
+    pub fn sender(&self) -> Option<&dyn std::any::Any> {
+        self.sender.map(|s| s as &dyn std::any::Any)
+    }
+
+    /// The metadata map attached via [`with_metadata`](PublishContext::with_metadata).
+    pub fn metadata(&self) -> &std::collections::HashMap<String, String> {
+        &self.metadata
+    }
+}
+
+/// An RAII guard returned by a generated `subscribe_A` method, for both sync- and async-mode
+/// hubs.
+///
+/// A `Subscription` is the only thing keeping its subscriber registered with the hub: once it is
+/// dropped, the subscriber is removed from the hub's subscription list, the same way `iced`'s
+/// `Subscription` exists only as long as something holds it. Call [`Subscription::forget`] to opt
+/// back into the old always-registered behavior.
+///
+/// `Subscription<T>` derefs to the `Arc<T>` it wraps, so existing code that locks the returned
+/// handle to reach the subscriber (for `as_any` downcasting, say) keeps working unchanged. `T` is
+/// the per-subscriber lock the hub stores: a generated sync `subscribe_A` returns
+/// `Subscription<std::sync::Mutex<Box<dyn Subscriber<A>>>>`, and a generated async `subscribe_A`
+/// returns `Subscription<tokio::sync::Mutex<Box<dyn AsyncSubscriber<A> + Send>>>` (the async hub
+/// holds each subscriber's lock across the `.await` of its `receive`, which a
+/// `tokio::sync::Mutex` allows).
+///
+/// # Examples
+/// ```
+/// # use pubsubhub_macros::{as_any, publishes};
+/// # use pubsubhub::Subscriber;
+/// struct Food { amount: i32 }
+///
+/// struct Dog { total_eaten: i32 }
+/// impl Subscriber<Food> for Dog {
+///     fn receive(&mut self, event: &Food) {
+///         self.total_eaten += event.amount;
+///     }
+///     as_any!();
+/// }
+///
+/// #[publishes(Food)]
+/// struct PubSub { }
+///
+/// let mut pubsub = PubSub::new();
+/// {
+///     let _sub = pubsub.subscribe_Food(Box::new(Dog { total_eaten: 0 }));
+///     pubsub.publish_Food(&Food { amount: 1 });
+/// } // `_sub` drops here, unsubscribing the `Dog`.
+/// pubsub.publish_Food(&Food { amount: 1 }); // no subscribers left to receive this
+/// ```
+pub struct Subscription<T> {
+    subscriber: std::sync::Arc<T>,
+    container: std::sync::Weak<std::sync::Mutex<Vec<std::sync::Arc<T>>>>,
+    active: bool,
+}
+
+impl<T> Subscription<T> {
+    /// Used by the `publishes` macro to build the guard returned from a generated `subscribe_A`.
+    /// Not intended to be called directly.
+    #[doc(hidden)]
+    pub fn new(
+        subscriber: std::sync::Arc<T>,
+        container: std::sync::Weak<std::sync::Mutex<Vec<std::sync::Arc<T>>>>,
+    ) -> Self {
+        Self { subscriber, container, active: true }
+    }
+
+    /// Consumes the guard without unsubscribing, leaving the subscriber registered with the hub
+    /// for as long as the hub itself lives. This restores the pre-RAII behavior for callers that
+    /// don't want to track the guard's lifetime.
+    pub fn forget(mut self) {
+        self.active = false;
+    }
+}
+
+impl<T> std::ops::Deref for Subscription<T> {
+    type Target = std::sync::Arc<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.subscriber
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+        if let Some(container) = self.container.upgrade() {
+            let mut subs = container.lock().unwrap();
+            if let Some(idx) = subs.iter().position(|s| std::sync::Arc::ptr_eq(s, &self.subscriber)) {
+                subs.swap_remove(idx);
+            }
+        }
+    }
+}
+
+/// The asynchronous counterpart to [`Subscription`], returned by a generated `subscribe_A` method
+/// on an async-mode hub (`#[publishes(async, A)]`). A plain alias for [`Subscription`] wrapping
+/// the `tokio::sync::Mutex` an async hub stores its subscribers behind; see [`Subscription`] for
+/// the full behavior.
+pub type AsyncSubscription<E> = Subscription<tokio::sync::Mutex<Box<dyn AsyncSubscriber<E> + Send>>>;
+
+/// Shared bookkeeping behind [`OnceSubscriber`] and [`AsyncOnceSubscriber`]: wraps an inner
+/// subscriber alongside the "have I fired yet" flag both once-subscribers expose, so the two
+/// types differ only in how they drive `I`'s `receive` (sync vs `async fn`), not in how they
+/// track or report firing.
+struct OnceState<I> {
+    inner: I,
+    fired: std::sync::atomic::AtomicBool,
+}
+
+impl<I> OnceState<I> {
+    fn new(inner: I) -> Self {
+        Self { inner, fired: std::sync::atomic::AtomicBool::new(false) }
+    }
+
+    fn fired(&self) -> bool {
+        self.fired.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    fn mark_fired(&self) {
+        self.fired.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// Wraps a [`Subscriber`] so it fires at most once: after its first [`Subscriber::receive`], it
+/// reports itself as [`fired`](OnceSubscriber::fired) so the generated `publish_A` can sweep it
+/// out of the hub's subscription list. Used by a generated `subscribe_A_once` method; not
+/// typically constructed by hand.
+pub struct OnceSubscriber<E: 'static> {
+    state: OnceState<Box<dyn Subscriber<E>>>,
+}
+
+impl<E: 'static> OnceSubscriber<E> {
+    pub fn new(inner: Box<dyn Subscriber<E>>) -> Self {
+        Self { state: OnceState::new(inner) }
+    }
+
+    /// Whether this subscriber has already received its one event.
+    pub fn fired(&self) -> bool {
+        self.state.fired()
+    }
+}
+
+impl<E: 'static> Subscriber<E> for OnceSubscriber<E> {
+    fn receive(&mut self, event: &E) {
+        if self.state.fired() {
+            return;
+        }
+        self.state.inner.receive(event);
+        self.state.mark_fired();
+    }
+
+    fn receive_with(&mut self, event: &E, ctx: &PublishContext) {
+        if self.state.fired() {
+            return;
+        }
+        self.state.inner.receive_with(event, ctx);
+        self.state.mark_fired();
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// The asynchronous counterpart to [`OnceSubscriber`], wrapping an [`AsyncSubscriber`] so it fires
+/// at most once. Used by a generated `subscribe_A_once` method on an async-mode hub.
+pub struct AsyncOnceSubscriber<E: Send + Sync + 'static> {
+    state: OnceState<Box<dyn AsyncSubscriber<E> + Send>>,
+}
+
+impl<E: Send + Sync + 'static> AsyncOnceSubscriber<E> {
+    pub fn new(inner: Box<dyn AsyncSubscriber<E> + Send>) -> Self {
+        Self { state: OnceState::new(inner) }
+    }
+
+    /// Whether this subscriber has already received its one event.
+    pub fn fired(&self) -> bool {
+        self.state.fired()
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Send + Sync + 'static> AsyncSubscriber<E> for AsyncOnceSubscriber<E> {
+    async fn receive(&mut self, event: &E) {
+        if self.state.fired() {
+            return;
+        }
+        self.state.inner.receive(event).await;
+        self.state.mark_fired();
+    }
+
+    async fn receive_with(&mut self, event: &E, ctx: &PublishContext) {
+        if self.state.fired() {
+            return;
+        }
+        self.state.inner.receive_with(event, ctx).await;
+        self.state.mark_fired();
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 }
\ No newline at end of file
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use pubsubhub::*;
+    use pubsubhub_macros::*;
+
+    struct A { a: i32 }
+
+    struct Listener { total: i32 }
+
+    #[async_trait]
+    impl AsyncSubscriber<A> for Listener {
+        async fn receive(&mut self, event: &A) {
+            self.total += event.a;
+        }
+        as_any!();
+    }
+
+    #[publishes(async, A)]
+    struct PubSub { }
+
+    #[tokio::test]
+    pub async fn test_can_publish_async() {
+        let mut pubsub = PubSub::new();
+        let l = Listener { total: 0 };
+        let l_arc = pubsub.subscribe_A(Box::new(l));
+
+        pubsub.publish_A(&A { a: 1 }).await;
+        pubsub.publish_A(&A { a: 2 }).await;
+
+        let boxed = l_arc.lock().await;
+        let l = boxed.as_any().downcast_ref::<Listener>().unwrap();
+        assert_eq!(l.total, 3);
+    }
+
+    #[tokio::test]
+    pub async fn test_can_publish_async_concurrent() {
+        let mut pubsub = PubSub::new();
+        let sub1 = pubsub.subscribe_A(Box::new(Listener { total: 0 }));
+        let sub2 = pubsub.subscribe_A(Box::new(Listener { total: 0 }));
+
+        pubsub.publish_A_concurrent(&A { a: 5 }).await;
+
+        let boxed1 = sub1.lock().await;
+        assert_eq!(boxed1.as_any().downcast_ref::<Listener>().unwrap().total, 5);
+
+        let boxed2 = sub2.lock().await;
+        assert_eq!(boxed2.as_any().downcast_ref::<Listener>().unwrap().total, 5);
+    }
+
+    #[tokio::test]
+    pub async fn test_subscribe_once_is_removed_after_first_publish_async() {
+        let mut pubsub = PubSub::new();
+        let sub = pubsub.subscribe_A_once(Box::new(Listener { total: 0 }));
+
+        assert_eq!(pubsub.subscription_count_A(), 1);
+
+        pubsub.publish_A(&A { a: 1 }).await;
+        assert_eq!(pubsub.subscription_count_A(), 0);
+
+        sub.forget();
+    }
+
+    #[tokio::test]
+    pub async fn test_clear_and_clear_all_async() {
+        let mut pubsub = PubSub::new();
+        pubsub.subscribe_A(Box::new(Listener { total: 0 })).forget();
+        pubsub.subscribe_A(Box::new(Listener { total: 0 })).forget();
+
+        assert_eq!(pubsub.subscription_count_A(), 2);
+
+        pubsub.clear_A();
+        assert_eq!(pubsub.subscription_count_A(), 0);
+
+        pubsub.subscribe_A(Box::new(Listener { total: 0 })).forget();
+        pubsub.clear_all();
+        assert_eq!(pubsub.subscription_count_A(), 0);
+    }
+
+    #[tokio::test]
+    pub async fn test_subscribe_once_is_removed_after_concurrent_publish() {
+        let mut pubsub = PubSub::new();
+        let sub = pubsub.subscribe_A_once(Box::new(Listener { total: 0 }));
+
+        assert_eq!(pubsub.subscription_count_A(), 1);
+
+        pubsub.publish_A_concurrent(&A { a: 1 }).await;
+        assert_eq!(pubsub.subscription_count_A(), 0);
+
+        sub.forget();
+    }
+
+    struct RoutingListener { seen_metadata: Option<String> }
+
+    #[async_trait]
+    impl AsyncSubscriber<A> for RoutingListener {
+        async fn receive(&mut self, _event: &A) {
+            panic!("receive_with should have been called instead of receive");
+        }
+        async fn receive_with(&mut self, _event: &A, ctx: &PublishContext) {
+            self.seen_metadata = ctx.metadata().get("reason").cloned();
+        }
+        as_any!();
+    }
+
+    #[tokio::test]
+    pub async fn test_publish_from_forwards_context_async() {
+        let mut pubsub = PubSub::new();
+        let sub = pubsub.subscribe_A(Box::new(RoutingListener { seen_metadata: None }));
+
+        let sender = 42i32;
+        let ctx = PublishContext::new()
+            .with_sender(&sender)
+            .with_metadata("reason", "retry");
+        pubsub.publish_A_from(&A { a: 1 }, &ctx).await;
+
+        let boxed = sub.lock().await;
+        let listener = boxed.as_any().downcast_ref::<RoutingListener>().unwrap();
+        assert_eq!(listener.seen_metadata.as_deref(), Some("retry"));
+    }
+}
@@ -4,7 +4,7 @@ mod tests {
     use pubsubhub_macros::*;
 
     struct A { a: i32 }
-    
+
     struct Listener { }
     impl Subscriber<A> for Listener {
         fn receive(&mut self, event: &A) {
@@ -20,19 +20,19 @@ mod tests {
     pub fn test_can_subscribe() {
         let mut pubsub = PubSub::new();
         let l = Listener { };
-        pubsub.subscribe_A(Box::new(l));
+        let _sub1 = pubsub.subscribe_A(Box::new(l));
 
-        assert_eq!(pubsub.__subscriptions_A.iter().count(), 1);
+        assert_eq!(pubsub.__subscriptions_A.lock().unwrap().iter().count(), 1);
 
-        pubsub.subscribe_A(Box::new(Listener { }));
-        assert_eq!(pubsub.__subscriptions_A.iter().count(), 2);
+        let _sub2 = pubsub.subscribe_A(Box::new(Listener { }));
+        assert_eq!(pubsub.__subscriptions_A.lock().unwrap().iter().count(), 2);
     }
 
     #[test]
     pub fn test_can_publish() {
         let mut pubsub = PubSub::new();
         let l = Listener { };
-        pubsub.subscribe_A(Box::new(l));
+        let _sub = pubsub.subscribe_A(Box::new(l));
 
         for i in 0..122 {
             let event = A { a: i };
@@ -45,24 +45,134 @@ mod tests {
     }
 
     #[test]
-    pub fn test_can_unsubscribe() {
+    pub fn test_subscription_unsubscribes_on_drop() {
         let mut pubsub = PubSub::new();
         let l1 = Listener { };
         let l2 = Listener { };
 
-        let l1_arc = pubsub.subscribe_A(Box::new(l1));
-        let l2_arc = pubsub.subscribe_A(Box::new(l2));
+        let sub1 = pubsub.subscribe_A(Box::new(l1));
+        let sub2 = pubsub.subscribe_A(Box::new(l2));
 
-        assert_eq!(pubsub.__subscriptions_A.iter().count(), 2);
+        assert_eq!(pubsub.__subscriptions_A.lock().unwrap().iter().count(), 2);
 
-        pubsub.unsubscribe_A(&l1_arc);
+        drop(sub1);
 
-        assert_eq!(pubsub.__subscriptions_A.iter().count(), 1);
+        assert_eq!(pubsub.__subscriptions_A.lock().unwrap().iter().count(), 1);
 
-        pubsub.unsubscribe_A(&l2_arc);
+        drop(sub2);
 
-        assert_eq!(pubsub.__subscriptions_A.iter().count(), 0);
+        assert_eq!(pubsub.__subscriptions_A.lock().unwrap().iter().count(), 0);
 
         pubsub.publish_A(&A { a: 123 });
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn test_subscription_forget_keeps_subscriber_registered() {
+        let mut pubsub = PubSub::new();
+        let sub = pubsub.subscribe_A(Box::new(Listener { }));
+
+        sub.forget();
+
+        assert_eq!(pubsub.__subscriptions_A.lock().unwrap().iter().count(), 1);
+    }
+
+    #[test]
+    pub fn test_subscribe_once_is_removed_after_first_publish() {
+        let mut pubsub = PubSub::new();
+        let sub = pubsub.subscribe_A_once(Box::new(Listener { }));
+
+        assert_eq!(pubsub.subscription_count_A(), 1);
+
+        pubsub.publish_A(&A { a: 1 });
+        assert_eq!(pubsub.subscription_count_A(), 0);
+
+        sub.forget();
+    }
+
+    #[test]
+    pub fn test_clear_and_clear_all() {
+        let mut pubsub = PubSub::new();
+        pubsub.subscribe_A(Box::new(Listener { })).forget();
+        pubsub.subscribe_A(Box::new(Listener { })).forget();
+
+        assert_eq!(pubsub.subscription_count_A(), 2);
+
+        pubsub.clear_A();
+        assert_eq!(pubsub.subscription_count_A(), 0);
+
+        pubsub.subscribe_A(Box::new(Listener { })).forget();
+        pubsub.clear_all();
+        assert_eq!(pubsub.subscription_count_A(), 0);
+    }
+
+    struct RoutingListener { seen_metadata: Option<String> }
+    impl Subscriber<A> for RoutingListener {
+        fn receive(&mut self, _event: &A) {
+            panic!("receive_with should have been called instead of receive");
+        }
+        fn receive_with(&mut self, _event: &A, ctx: &PublishContext) {
+            self.seen_metadata = ctx.metadata().get("reason").cloned();
+        }
+        as_any!();
+    }
+
+    #[test]
+    pub fn test_publish_from_forwards_context() {
+        let mut pubsub = PubSub::new();
+        let sub = pubsub.subscribe_A(Box::new(RoutingListener { seen_metadata: None }));
+
+        let sender = 42i32;
+        let ctx = PublishContext::new()
+            .with_sender(&sender)
+            .with_metadata("reason", "retry");
+        pubsub.publish_A_from(&A { a: 1 }, &ctx);
+
+        let boxed = sub.lock().unwrap();
+        let listener = boxed.as_any().downcast_ref::<RoutingListener>().unwrap();
+        assert_eq!(listener.seen_metadata.as_deref(), Some("retry"));
+    }
+
+    struct CountingListener { count: u32 }
+    impl Subscriber<A> for CountingListener {
+        fn receive(&mut self, _event: &A) {
+            self.count += 1;
+        }
+        as_any!();
+    }
+
+    #[test]
+    pub fn test_publish_checked_delivers_past_a_panicking_subscriber() {
+        let mut pubsub = PubSub::new();
+        let _sub1 = pubsub.subscribe_A(Box::new(Listener { }));
+        let sub2 = pubsub.subscribe_A(Box::new(CountingListener { count: 0 }));
+
+        let errors = pubsub.publish_A_checked(&A { a: 123 });
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].index, 0);
+
+        let boxed = sub2.lock().unwrap();
+        assert_eq!(boxed.as_any().downcast_ref::<CountingListener>().unwrap().count, 1);
+    }
+
+    struct DroppingListener {
+        other: Option<Subscription<std::sync::Mutex<Box<dyn Subscriber<A>>>>>,
+    }
+    impl Subscriber<A> for DroppingListener {
+        fn receive(&mut self, _event: &A) {
+            self.other.take();
+        }
+        as_any!();
+    }
+
+    #[test]
+    pub fn test_publish_does_not_deadlock_when_a_subscriber_drops_another_subscription() {
+        let mut pubsub = PubSub::new();
+        let other = pubsub.subscribe_A(Box::new(Listener { }));
+        let _dropper = pubsub.subscribe_A(Box::new(DroppingListener { other: Some(other) }));
+
+        pubsub.publish_A(&A { a: 1 });
+
+        assert_eq!(pubsub.subscription_count_A(), 1);
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use pubsubhub::*;
+    use pubsubhub_macros::*;
+
+    struct Food { amount: i32 }
+    struct Sleep { }
+
+    struct Dog { total_eaten: i32, times_slept: u32 }
+    impl Subscriber<Food> for Dog {
+        fn receive(&mut self, event: &Food) {
+            self.total_eaten += event.amount;
+        }
+        as_any!();
+    }
+    impl Subscriber<Sleep> for Dog {
+        fn receive(&mut self, _event: &Sleep) {
+            self.times_slept += 1;
+        }
+        as_any!();
+    }
+
+    #[test]
+    pub fn test_bus_dispatches_by_type() {
+        let bus = Bus::new();
+        // Dog implements Subscriber for both Food and Sleep, so each subscribe::<E> call needs a
+        // turbofish to pick which impl's vtable to register; the two calls land in independent
+        // TypeId channels, each with its own Dog instance.
+        let food_sub = bus.subscribe::<Food>(Box::new(Dog { total_eaten: 0, times_slept: 0 }));
+        let sleep_sub = bus.subscribe::<Sleep>(Box::new(Dog { total_eaten: 0, times_slept: 0 }));
+
+        bus.publish(&Food { amount: 4 });
+        bus.publish(&Sleep { });
+        bus.publish(&Food { amount: 1 });
+
+        let food_dog = food_sub.lock().unwrap();
+        assert_eq!(food_dog.as_any().downcast_ref::<Dog>().unwrap().total_eaten, 5);
+
+        let sleep_dog = sleep_sub.lock().unwrap();
+        assert_eq!(sleep_dog.as_any().downcast_ref::<Dog>().unwrap().times_slept, 1);
+    }
+
+    #[test]
+    pub fn test_bus_unsubscribes_on_drop() {
+        let bus = Bus::new();
+        let sub = bus.subscribe::<Food>(Box::new(Dog { total_eaten: 0, times_slept: 0 }));
+        let dog = (*sub).clone();
+
+        drop(sub);
+
+        // No subscribers left, so the dropped Dog must not see this event.
+        bus.publish(&Food { amount: 4 });
+
+        let boxed = dog.lock().unwrap();
+        assert_eq!(boxed.as_any().downcast_ref::<Dog>().unwrap().total_eaten, 0);
+    }
+
+    #[test]
+    pub fn test_bus_publish_with_no_subscribers_is_a_no_op() {
+        let bus = Bus::new();
+        bus.publish(&Food { amount: 4 });
+    }
+}